@@ -1,18 +1,28 @@
 use lazy_static::lazy_static;
 use parking_lot::Mutex;
 use std::sync::{
-    atomic::{AtomicIsize, Ordering},
+    atomic::{AtomicIsize, AtomicU32, Ordering},
     Arc,
 };
 use windows::Win32::{
     Devices::Display::GUID_DEVINTERFACE_MONITOR,
     Foundation::{HWND, LPARAM, LRESULT, WPARAM},
-    System::LibraryLoader::{GetProcAddress, LoadLibraryW},
+    System::{
+        LibraryLoader::{GetProcAddress, LoadLibraryW},
+        Power::{
+            RegisterSuspendResumeNotification, UnregisterSuspendResumeNotification, HPOWERNOTIFY,
+        },
+        RemoteDesktop::{
+            WTSRegisterSessionNotification, WTSUnRegisterSessionNotification,
+            NOTIFY_FOR_ALL_SESSIONS,
+        },
+    },
     UI::WindowsAndMessaging::{
         CreateWindowExW, DefWindowProcW, DispatchMessageW, FindWindowExW, GetMessageW,
-        PostQuitMessage, RegisterClassW, RegisterDeviceNotificationW, TranslateMessage,
-        DBT_DEVTYP_DEVICEINTERFACE, DEVICE_NOTIFY_WINDOW_HANDLE, DEV_BROADCAST_DEVICEINTERFACE_W,
-        MSG, WINDOW_STYLE, WM_DESTROY, WNDCLASSW, WS_EX_TOPMOST,
+        PostQuitMessage, RegisterClassW, RegisterDeviceNotificationW, RegisterWindowMessageW,
+        TranslateMessage, DBT_DEVTYP_DEVICEINTERFACE, DEVICE_NOTIFY_WINDOW_HANDLE,
+        DEV_BROADCAST_DEVICEINTERFACE_W, MSG, WINDOW_STYLE, WM_DESTROY, WM_ENDSESSION, WNDCLASSW,
+        WS_EX_TOPMOST,
     },
 };
 
@@ -22,7 +32,11 @@ use crate::{
     utils::spawn_named_thread,
 };
 
-use super::{string_utils::WindowsString, WindowsApi};
+use super::{
+    string_utils::WindowsString,
+    win_event_hook::{install_win_event_hooks, uninstall_win_event_hooks},
+    WindowsApi,
+};
 
 type Callback = Box<dyn Fn(u32, usize, isize) -> Result<()> + Send + Sync + 'static>;
 
@@ -32,17 +46,57 @@ lazy_static! {
 
 pub static BACKGROUND_HWND: AtomicIsize = AtomicIsize::new(0);
 
+/// registered message id for `"SeelenUIHookEvent"`, used by the injected `hook.dll` to deliver
+/// `WH_CALLWNDPROC`/`WH_CALLWNDPROCRET`/`WH_GETMESSAGE` events it observes in the native shell
+/// process back to this one
+pub static HOOK_EVENT_MSG_ID: AtomicU32 = AtomicU32::new(0);
+
+/// registered message id for `"SeelenUIHookCbtEvent"`, used by `hook.dll` to deliver `WH_CBT`
+/// lifecycle subcodes. Kept distinct from `HOOK_EVENT_MSG_ID` because CBT subcodes and `WM_*`
+/// codes overlap numerically (e.g. `HCBT_CREATEWND` == `WM_MOVE` == 3)
+pub static HOOK_CBT_EVENT_MSG_ID: AtomicU32 = AtomicU32::new(0);
+
+/// handle returned by `RegisterSuspendResumeNotification`, needed to unregister on shutdown
+static SUSPEND_RESUME_HANDLE: AtomicIsize = AtomicIsize::new(0);
+
+unsafe fn unregister_background_window_notifications(hwnd: HWND) {
+    let power_handle = SUSPEND_RESUME_HANDLE.swap(0, Ordering::Relaxed);
+    if power_handle != 0 {
+        log_error!(UnregisterSuspendResumeNotification(HPOWERNOTIFY(
+            power_handle
+        )));
+    }
+    log_error!(WTSUnRegisterSessionNotification(hwnd));
+}
+
 unsafe extern "system" fn window_proc(
     hwnd: HWND,
     msg: u32,
     w_param: WPARAM,
     l_param: LPARAM,
 ) -> LRESULT {
-    if msg == WM_DESTROY {
+    if msg == WM_DESTROY || (msg == WM_ENDSESSION && w_param.0 != 0) {
+        unregister_background_window_notifications(hwnd);
         PostQuitMessage(0);
         return LRESULT(0);
     }
 
+    if msg != 0 && msg == HOOK_EVENT_MSG_ID.load(Ordering::Relaxed) {
+        log::trace!(
+            "Received hook.dll event, window: {:08X}, message: {:08X}",
+            w_param.0,
+            l_param.0
+        );
+    }
+
+    if msg != 0 && msg == HOOK_CBT_EVENT_MSG_ID.load(Ordering::Relaxed) {
+        log::trace!(
+            "Received hook.dll CBT event, window: {:08X}, subcode: {:08X}",
+            w_param.0,
+            l_param.0
+        );
+    }
+
     for callback in CALLBACKS.lock().iter() {
         log_error!(callback(msg, w_param.0, l_param.0));
     }
@@ -84,6 +138,17 @@ unsafe fn _create_background_window(done: &crossbeam_channel::Sender<()>) -> Res
     let handle: isize = hwnd.0 as isize;
     BACKGROUND_HWND.store(handle, Ordering::Relaxed);
 
+    // register the messages hook.dll uses to deliver events from the native shell process
+    {
+        let event_msg = WindowsString::from("SeelenUIHookEvent");
+        let msg_id = RegisterWindowMessageW(event_msg.as_pcwstr());
+        HOOK_EVENT_MSG_ID.store(msg_id, Ordering::Relaxed);
+
+        let cbt_event_msg = WindowsString::from("SeelenUIHookCbtEvent");
+        let cbt_msg_id = RegisterWindowMessageW(cbt_event_msg.as_pcwstr());
+        HOOK_CBT_EVENT_MSG_ID.store(cbt_msg_id, Ordering::Relaxed);
+    }
+
     // register window to recieve device notifications for monitor changes
     {
         let mut notification_filter = DEV_BROADCAST_DEVICEINTERFACE_W {
@@ -100,6 +165,20 @@ unsafe fn _create_background_window(done: &crossbeam_channel::Sender<()>) -> Res
         )?;
     }
 
+    // register window to receive WM_POWERBROADCAST notifications (suspend/resume)
+    {
+        let power_notify =
+            RegisterSuspendResumeNotification(hwnd.into(), DEVICE_NOTIFY_WINDOW_HANDLE)?;
+        SUSPEND_RESUME_HANDLE.store(power_notify.0, Ordering::Relaxed);
+    }
+
+    // register window to receive WM_WTSSESSION_CHANGE notifications (lock/unlock/logon)
+    WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_ALL_SESSIONS)?;
+
+    // install out-of-context WinEvent hooks, delivery is driven by this same thread's
+    // GetMessageW loop below, no DLL injection required
+    install_win_event_hooks();
+
     done.send(())?;
     let mut msg = MSG::default();
     // GetMessageW will run until PostQuitMessage(0) is called
@@ -107,6 +186,7 @@ unsafe fn _create_background_window(done: &crossbeam_channel::Sender<()>) -> Res
         TranslateMessage(&msg).ok().filter_fake_error()?;
         DispatchMessageW(&msg);
     }
+    uninstall_win_event_hooks();
     Ok(())
 }
 
@@ -114,7 +194,9 @@ pub unsafe fn test_dll_hook() -> Result<()> {
     let dll_path = WindowsString::from("hook.dll");
     let dll = LoadLibraryW(dll_path.as_pcwstr())?;
 
-    let install_hook: unsafe extern "system" fn(u32) -> bool =
+    // install_hook returns a HookInstallStatus, exposed across the FFI boundary as its
+    // underlying i32 repr since the enum itself lives in the hook.dll crate
+    let install_hook: unsafe extern "system" fn(u32) -> i32 =
         std::mem::transmute(GetProcAddress(dll, windows_core::s!("install_hook")));
 
     let native_shell = get_native_shell_hwnd()?;
@@ -126,7 +208,8 @@ pub unsafe fn test_dll_hook() -> Result<()> {
         process_id
     );
 
-    install_hook(thread_id);
+    let status = install_hook(thread_id);
+    log::debug!("install_hook returned status {status}");
 
     let mut msg = MSG::default();
     while GetMessageW(&mut msg, None, 0, 0).into() {
@@ -138,7 +221,8 @@ pub unsafe fn test_dll_hook() -> Result<()> {
 }
 
 /// the objective with this window is having a thread that will receive window events
-/// and propagate them across the application (common events are keyboard, power, display, etc)
+/// and propagate them across the application (common events are power, session and
+/// display changes; keyboard/mouse input is handled separately by `input_hook`)
 pub fn create_background_window() -> Result<()> {
     spawn_named_thread("DLL", || {
         log_error!(unsafe { test_dll_hook() });
@@ -0,0 +1,197 @@
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use windows::Win32::{
+    Foundation::{LPARAM, LRESULT, WPARAM},
+    UI::{
+        Input::KeyboardAndMouse::{
+            VIRTUAL_KEY, VK_CONTROL, VK_LCONTROL, VK_LMENU, VK_LSHIFT, VK_LWIN, VK_MENU,
+            VK_RCONTROL, VK_RMENU, VK_RSHIFT, VK_RWIN, VK_SHIFT,
+        },
+        WindowsAndMessaging::{
+            CallNextHookEx, DispatchMessageW, GetMessageW, SetWindowsHookExW, TranslateMessage,
+            UnhookWindowsHookEx, HHOOK, KBDLLHOOKSTRUCT, MSG, MSLLHOOKSTRUCT, WH_KEYBOARD_LL,
+            WH_MOUSE_LL, WM_KEYDOWN, WM_KEYUP, WM_SYSKEYDOWN, WM_SYSKEYUP,
+        },
+    },
+};
+
+use crate::{
+    error_handler::{Result, WindowsResultExt},
+    log_error, trace_lock,
+    utils::spawn_named_thread,
+};
+
+/// state of the well known modifier keys, tracked from the raw key stream since the low level
+/// hook sees every keypress system-wide, even when this process has no focus
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub win: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum InputEvent {
+    Keyboard {
+        message: u32,
+        vk_code: u32,
+        modifiers: Modifiers,
+    },
+    Mouse {
+        message: u32,
+        x: i32,
+        y: i32,
+    },
+}
+
+/// returning `true` consumes the event so it never reaches the focused application, used to
+/// implement global hotkeys
+type Callback = Box<dyn Fn(InputEvent) -> Result<bool> + Send + Sync + 'static>;
+
+lazy_static! {
+    static ref CALLBACKS: Arc<Mutex<Vec<Callback>>> = Arc::new(Mutex::new(Vec::new()));
+}
+
+static CTRL_DOWN: AtomicBool = AtomicBool::new(false);
+static ALT_DOWN: AtomicBool = AtomicBool::new(false);
+static SHIFT_DOWN: AtomicBool = AtomicBool::new(false);
+static WIN_DOWN: AtomicBool = AtomicBool::new(false);
+
+static mut KEYBOARD_HOOK: Option<HHOOK> = None;
+static mut MOUSE_HOOK: Option<HHOOK> = None;
+
+fn current_modifiers() -> Modifiers {
+    Modifiers {
+        ctrl: CTRL_DOWN.load(Ordering::Relaxed),
+        alt: ALT_DOWN.load(Ordering::Relaxed),
+        shift: SHIFT_DOWN.load(Ordering::Relaxed),
+        win: WIN_DOWN.load(Ordering::Relaxed),
+    }
+}
+
+fn update_modifier_state(vk_code: u32, is_down: bool) {
+    match VIRTUAL_KEY(vk_code as u16) {
+        VK_CONTROL | VK_LCONTROL | VK_RCONTROL => CTRL_DOWN.store(is_down, Ordering::Relaxed),
+        VK_MENU | VK_LMENU | VK_RMENU => ALT_DOWN.store(is_down, Ordering::Relaxed),
+        VK_SHIFT | VK_LSHIFT | VK_RSHIFT => SHIFT_DOWN.store(is_down, Ordering::Relaxed),
+        VK_LWIN | VK_RWIN => WIN_DOWN.store(is_down, Ordering::Relaxed),
+        _ => {}
+    }
+}
+
+/// forwards `event` to every subscriber, returns `true` if any of them consumed it
+fn dispatch(event: InputEvent) -> bool {
+    let mut consumed = false;
+    for callback in CALLBACKS.lock().iter() {
+        match callback(event) {
+            Ok(true) => consumed = true,
+            Ok(false) => {}
+            Err(err) => log::error!("{err}"),
+        }
+    }
+    consumed
+}
+
+unsafe extern "system" fn keyboard_proc(n_code: i32, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
+    if n_code < 0 {
+        return CallNextHookEx(None, n_code, w_param, l_param);
+    }
+
+    let message = w_param.0 as u32;
+    if let Some(data) = (l_param.0 as *const KBDLLHOOKSTRUCT).as_ref() {
+        if matches!(message, WM_KEYDOWN | WM_KEYUP | WM_SYSKEYDOWN | WM_SYSKEYUP) {
+            let is_down = matches!(message, WM_KEYDOWN | WM_SYSKEYDOWN);
+            update_modifier_state(data.vkCode, is_down);
+        }
+
+        let consumed = dispatch(InputEvent::Keyboard {
+            message,
+            vk_code: data.vkCode,
+            modifiers: current_modifiers(),
+        });
+        if consumed {
+            return LRESULT(1);
+        }
+    }
+
+    CallNextHookEx(None, n_code, w_param, l_param)
+}
+
+unsafe extern "system" fn mouse_proc(n_code: i32, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
+    if n_code < 0 {
+        return CallNextHookEx(None, n_code, w_param, l_param);
+    }
+
+    if let Some(data) = (l_param.0 as *const MSLLHOOKSTRUCT).as_ref() {
+        let consumed = dispatch(InputEvent::Mouse {
+            message: w_param.0 as u32,
+            x: data.pt.x,
+            y: data.pt.y,
+        });
+        if consumed {
+            return LRESULT(1);
+        }
+    }
+
+    CallNextHookEx(None, n_code, w_param, l_param)
+}
+
+/// will lock until the hooks are uninstalled
+unsafe fn _install_input_hooks(done: &crossbeam_channel::Sender<()>) -> Result<()> {
+    // low level hooks are installed with a null module handle and a thread id of 0, they run
+    // in this process rather than being injected
+    KEYBOARD_HOOK = Some(SetWindowsHookExW(
+        WH_KEYBOARD_LL,
+        Some(keyboard_proc),
+        None,
+        0,
+    )?);
+    MOUSE_HOOK = match SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_proc), None, 0) {
+        Ok(hook) => Some(hook),
+        Err(err) => {
+            if let Some(hook) = KEYBOARD_HOOK.take() {
+                UnhookWindowsHookEx(hook)?;
+            }
+            return Err(err.into());
+        }
+    };
+
+    done.send(())?;
+    let mut msg = MSG::default();
+    // GetMessageW will run until PostQuitMessage(0) is called
+    while GetMessageW(&mut msg, None, 0, 0).into() {
+        TranslateMessage(&msg).ok().filter_fake_error()?;
+        DispatchMessageW(&msg);
+    }
+
+    if let Some(hook) = KEYBOARD_HOOK.take() {
+        UnhookWindowsHookEx(hook)?;
+    }
+    if let Some(hook) = MOUSE_HOOK.take() {
+        UnhookWindowsHookEx(hook)?;
+    }
+    Ok(())
+}
+
+/// installs global `WH_KEYBOARD_LL`/`WH_MOUSE_LL` hooks on a dedicated thread with its own
+/// message pump, mirroring `create_background_window`
+pub fn install_input_hooks() -> Result<()> {
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    spawn_named_thread("Input Hook", move || {
+        log_error!(unsafe { _install_input_hooks(&tx) });
+    })?;
+    rx.recv()?;
+    Ok(())
+}
+
+pub fn subscribe_to_input_events<F>(callback: F)
+where
+    F: Fn(InputEvent) -> Result<bool> + Send + Sync + 'static,
+{
+    trace_lock!(CALLBACKS).push(Box::new(callback));
+}
@@ -0,0 +1,94 @@
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use windows::Win32::{
+    Foundation::HWND,
+    UI::{
+        Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
+        WindowsAndMessaging::{
+            EVENT_OBJECT_CREATE, EVENT_OBJECT_DESTROY, EVENT_OBJECT_LOCATIONCHANGE,
+            EVENT_OBJECT_NAMECHANGE, EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_MINIMIZEEND,
+            EVENT_SYSTEM_MINIMIZESTART, OBJID_WINDOW, WINEVENT_OUTOFCONTEXT,
+        },
+    },
+};
+
+use crate::{error_handler::Result, log_error, trace_lock};
+
+/// `CHILDID_SELF`, used to filter out events from child/control objects and keep only
+/// events emitted by the top-level window itself
+const CHILDID_SELF: i32 = 0;
+
+type Callback = Box<dyn Fn(u32, HWND) -> Result<()> + Send + Sync + 'static>;
+
+lazy_static! {
+    static ref CALLBACKS: Arc<Mutex<Vec<Callback>>> = Arc::new(Mutex::new(Vec::new()));
+    static ref WIN_EVENT_HOOKS: Arc<Mutex<Vec<HWINEVENTHOOK>>> = Arc::new(Mutex::new(Vec::new()));
+}
+
+/// (min, max) event id ranges we install a `WINEVENT_OUTOFCONTEXT` hook for, covering window
+/// creation/destruction, move/resize, foreground changes, minimize state and title changes
+const EVENT_RANGES: &[(u32, u32)] = &[
+    (EVENT_OBJECT_CREATE, EVENT_OBJECT_DESTROY),
+    (EVENT_OBJECT_LOCATIONCHANGE, EVENT_OBJECT_LOCATIONCHANGE),
+    (EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_FOREGROUND),
+    (EVENT_SYSTEM_MINIMIZESTART, EVENT_SYSTEM_MINIMIZEEND),
+    (EVENT_OBJECT_NAMECHANGE, EVENT_OBJECT_NAMECHANGE),
+];
+
+unsafe extern "system" fn win_event_proc(
+    _h_win_event_hook: HWINEVENTHOOK,
+    event: u32,
+    hwnd: HWND,
+    id_object: i32,
+    id_child: i32,
+    _event_thread: u32,
+    _event_time: u32,
+) {
+    // only top-level windows matter to us, skip events coming from child objects/controls
+    if id_object != OBJID_WINDOW.0 || id_child != CHILDID_SELF || hwnd.0.is_null() {
+        return;
+    }
+
+    for callback in CALLBACKS.lock().iter() {
+        log_error!(callback(event, hwnd));
+    }
+}
+
+/// installs an out-of-context `WinEventHook` for each range in `EVENT_RANGES`. Since the hook is
+/// `WINEVENT_OUTOFCONTEXT` the callback runs in our own process, on whatever thread is driving
+/// the message loop at delivery time, so no DLL injection is required
+pub unsafe fn install_win_event_hooks() {
+    let mut hooks = trace_lock!(WIN_EVENT_HOOKS);
+    for (min, max) in EVENT_RANGES {
+        let hook = SetWinEventHook(
+            *min,
+            *max,
+            None,
+            Some(win_event_proc),
+            0,
+            0,
+            WINEVENT_OUTOFCONTEXT,
+        );
+        if hook.is_invalid() {
+            log::warn!("Failed to install win event hook for range {min}-{max}");
+            continue;
+        }
+        hooks.push(hook);
+    }
+}
+
+/// unhooks every win event hook installed by [`install_win_event_hooks`]
+pub unsafe fn uninstall_win_event_hooks() {
+    let mut hooks = trace_lock!(WIN_EVENT_HOOKS);
+    for hook in hooks.drain(..) {
+        UnhookWinEvent(hook);
+    }
+}
+
+pub fn subscribe_to_win_events<F>(callback: F)
+where
+    F: Fn(u32, HWND) -> Result<()> + Send + Sync + 'static,
+{
+    trace_lock!(CALLBACKS).push(Box::new(callback));
+}
@@ -1,24 +1,103 @@
-use windows::Win32::{
-    Foundation::{BOOL, HINSTANCE, HWND, LPARAM, LRESULT, TRUE, WPARAM},
-    System::SystemServices::{
-        DLL_PROCESS_ATTACH, DLL_PROCESS_DETACH, DLL_THREAD_ATTACH, DLL_THREAD_DETACH,
-    },
-    UI::WindowsAndMessaging::{
-        CallNextHookEx, GetClassNameW, SetWindowsHookExW, CWPRETSTRUCT, CWPSTRUCT, HHOOK, MSG,
-        WH_CALLWNDPROC, WH_CALLWNDPROCRET, WH_GETMESSAGE,
+use windows::{
+    core::w,
+    Win32::{
+        Foundation::{BOOL, HINSTANCE, HWND, LPARAM, LRESULT, TRUE, WPARAM},
+        System::SystemServices::{
+            DLL_PROCESS_ATTACH, DLL_PROCESS_DETACH, DLL_THREAD_ATTACH, DLL_THREAD_DETACH,
+        },
+        UI::WindowsAndMessaging::{
+            CallNextHookEx, FindWindowExW, PostMessageW, RegisterWindowMessageW, SetWindowsHookExW,
+            UnhookWindowsHookEx, CWPRETSTRUCT, CWPSTRUCT, HCBT_ACTIVATE, HCBT_CREATEWND,
+            HCBT_DESTROYWND, HCBT_MINMAX, HCBT_MOVESIZE, HHOOK, MSG, WH_CALLWNDPROC,
+            WH_CALLWNDPROCRET, WH_CBT, WH_GETMESSAGE,
+        },
     },
 };
 
 static mut WIN_PROC_HOOK: Option<HHOOK> = None;
 static mut WIN_PROC_RET_HOOK: Option<HHOOK> = None;
 static mut GET_MESSAGE_HOOK: Option<HHOOK> = None;
+static mut CBT_HOOK: Option<HHOOK> = None;
 static mut DLL_HANDLE: Option<HINSTANCE> = None;
 
-fn get_class(hwnd: HWND) -> String {
-    let mut text: [u16; 512] = [0; 512];
-    let len = unsafe { GetClassNameW(hwnd, &mut text) };
-    let length = usize::try_from(len).unwrap_or(0);
-    String::from_utf16_lossy(&text[..length])
+/// status returned by `install_hook`/`install_cbt_hook` across the FFI boundary, a bare `bool`
+/// can't tell "couldn't install" apart from "already installed"
+#[repr(i32)]
+pub enum HookInstallStatus {
+    Installed = 0,
+    AlreadyInstalled = 1,
+    /// `WH_CALLWNDPROC`/`WH_CALLWNDPROCRET`/`WH_CBT` are thread-scoped hooks and require a
+    /// valid module handle, the real API returns `ERROR_HOOK_NEEDS_HMOD` in this situation
+    MissingModuleHandle = 2,
+    Failed = 3,
+}
+
+/// message id shared with the host process for `WH_CALLWNDPROC`/`WH_CALLWNDPROCRET`/
+/// `WH_GETMESSAGE` events, cached after the first lookup since `RegisterWindowMessageW` returns
+/// the same id for the same string across processes
+static mut HOST_EVENT_MSG: u32 = 0;
+
+/// message id shared with the host process for `WH_CBT` lifecycle subcodes, kept distinct from
+/// `HOST_EVENT_MSG` because CBT subcodes and `WM_*` codes overlap numerically (e.g.
+/// `HCBT_CREATEWND` == `WM_MOVE` == 3), so the host couldn't otherwise tell them apart
+static mut HOST_CBT_EVENT_MSG: u32 = 0;
+
+/// handle of the Seelen UI process' background window, cached after the first lookup since
+/// this hook proc runs on every hooked message in a foreign process and a system-wide
+/// `FindWindowExW` on each call would be a real latency hit there; re-resolved if a post fails,
+/// since the host window can be recreated (e.g. app restart)
+static mut HOST_HWND: isize = 0;
+
+/// posts the event to the Seelen UI process' background window, identified by its
+/// `"SeelenUIShell"` window class, so it can be propagated through its own callbacks
+unsafe fn post_event_to_host(msg_id: u32, event_hwnd: HWND, message: u32) {
+    let host_hwnd = match resolve_host_hwnd() {
+        Some(hwnd) => hwnd,
+        None => return,
+    };
+
+    if let Err(err) = PostMessageW(
+        Some(host_hwnd),
+        msg_id,
+        WPARAM(event_hwnd.0 as usize),
+        LPARAM(message as isize),
+    ) {
+        println!("Failed to post event to host: {}", err);
+        // the cached handle may be stale (e.g. the host window was destroyed and recreated),
+        // drop it so the next call re-resolves instead of posting to a dead window forever
+        HOST_HWND = 0;
+    }
+}
+
+unsafe fn resolve_host_hwnd() -> Option<HWND> {
+    if HOST_HWND != 0 {
+        return Some(HWND(HOST_HWND as _));
+    }
+
+    match FindWindowExW(None, None, w!("SeelenUIShell"), None) {
+        Ok(hwnd) => {
+            HOST_HWND = hwnd.0 as isize;
+            Some(hwnd)
+        }
+        Err(err) => {
+            println!("Failed to find host window: {}", err);
+            None
+        }
+    }
+}
+
+unsafe fn host_event_msg() -> u32 {
+    if HOST_EVENT_MSG == 0 {
+        HOST_EVENT_MSG = RegisterWindowMessageW(w!("SeelenUIHookEvent"));
+    }
+    HOST_EVENT_MSG
+}
+
+unsafe fn host_cbt_event_msg() -> u32 {
+    if HOST_CBT_EVENT_MSG == 0 {
+        HOST_CBT_EVENT_MSG = RegisterWindowMessageW(w!("SeelenUIHookCbtEvent"));
+    }
+    HOST_CBT_EVENT_MSG
 }
 
 /// # Safety
@@ -34,11 +113,7 @@ pub unsafe extern "system" fn win_proc_hook(
 
     let data = (l_param.0 as *const CWPSTRUCT).as_ref();
     if let Some(data) = data {
-        println!(
-            "win_proc_hook Window: {:08X} Class: {}",
-            data.hwnd.0 as usize,
-            get_class(data.hwnd),
-        );
+        post_event_to_host(host_event_msg(), data.hwnd, data.message);
     }
     CallNextHookEx(None, n_code, w_param, l_param)
 }
@@ -56,11 +131,7 @@ pub unsafe extern "system" fn win_proc_ret_hook(
 
     let data = (l_param.0 as *const CWPRETSTRUCT).as_ref();
     if let Some(data) = data {
-        println!(
-            "win_proc_ret_hook Window: {:08X} Class: {}",
-            data.hwnd.0 as usize,
-            get_class(data.hwnd),
-        );
+        post_event_to_host(host_event_msg(), data.hwnd, data.message);
     }
     CallNextHookEx(None, n_code, w_param, l_param)
 }
@@ -77,17 +148,77 @@ pub unsafe extern "system" fn get_message_hook(
     }
 
     let msg = (l_param.0 as *const MSG).as_ref().unwrap();
-    println!(
-        "get_message_hook Window: {:08X} Class: {}",
-        msg.hwnd.0 as usize,
-        get_class(msg.hwnd)
-    );
+    post_event_to_host(host_event_msg(), msg.hwnd, msg.message);
     CallNextHookEx(None, n_code, w_param, l_param)
 }
 
+/// Unlike the other hooks in this DLL, a `WH_CBT` proc fires before the action it reports on
+/// takes effect (window creation, activation, minimize/restore, move/resize). This hook is
+/// observe-only: it reports the subcode and target `hwnd` to the host the same way
+/// `win_proc_hook`/`get_message_hook` do, over the fire-and-forget `PostMessageW` based IPC, and
+/// always forwards to `CallNextHookEx`. It does not read or forward the structured payload behind
+/// `l_param` (e.g. `CBT_CREATEWND`'s `CREATESTRUCT` or the `RECT` of `HCBT_MOVESIZE`), and it
+/// cannot veto the action or pre-set a window's
+/// rectangle — both would require a synchronous reply from the host, which this IPC doesn't
+/// provide. That is out of scope here; revisit with a blocking channel (e.g. `SendMessageW`) if
+/// blocking/rect-snapping is needed later.
+///
 /// # Safety
 #[no_mangle]
-pub unsafe extern "system" fn install_hook(thread_id: u32) -> bool {
+pub unsafe extern "system" fn cbt_hook(n_code: i32, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
+    if n_code < 0 {
+        return CallNextHookEx(None, n_code, w_param, l_param);
+    }
+
+    match n_code as u32 {
+        HCBT_CREATEWND | HCBT_DESTROYWND | HCBT_ACTIVATE | HCBT_MINMAX | HCBT_MOVESIZE => {
+            post_event_to_host(host_cbt_event_msg(), HWND(w_param.0 as _), n_code as u32);
+        }
+        _ => {}
+    }
+
+    CallNextHookEx(None, n_code, w_param, l_param)
+}
+
+/// # Safety
+#[no_mangle]
+pub unsafe extern "system" fn install_cbt_hook(thread_id: u32) -> HookInstallStatus {
+    if CBT_HOOK.is_some() {
+        println!("CBT hook already installed");
+        return HookInstallStatus::AlreadyInstalled;
+    }
+
+    if DLL_HANDLE.is_none() {
+        println!("Cannot install CBT hook: missing module handle");
+        return HookInstallStatus::MissingModuleHandle;
+    }
+
+    println!("Installing CBT hook");
+
+    CBT_HOOK = match SetWindowsHookExW(WH_CBT, Some(cbt_hook), DLL_HANDLE, thread_id) {
+        Ok(hook) => Some(hook),
+        Err(err) => {
+            println!("Failed to install CBT hook: {}", err);
+            return HookInstallStatus::Failed;
+        }
+    };
+
+    HookInstallStatus::Installed
+}
+
+/// # Safety
+#[no_mangle]
+pub unsafe extern "system" fn install_hook(thread_id: u32) -> HookInstallStatus {
+    if WIN_PROC_HOOK.is_some() || WIN_PROC_RET_HOOK.is_some() || GET_MESSAGE_HOOK.is_some() {
+        println!("Hooks already installed");
+        return HookInstallStatus::AlreadyInstalled;
+    }
+
+    if DLL_HANDLE.is_none() {
+        println!("Cannot install hook: missing module handle");
+        return HookInstallStatus::MissingModuleHandle;
+    }
+
     println!("Installing hook");
 
     WIN_PROC_HOOK =
@@ -95,7 +226,7 @@ pub unsafe extern "system" fn install_hook(thread_id: u32) -> bool {
             Ok(hook) => Some(hook),
             Err(err) => {
                 println!("Failed to install hook: {}", err);
-                return false;
+                return HookInstallStatus::Failed;
             }
         };
 
@@ -108,7 +239,8 @@ pub unsafe extern "system" fn install_hook(thread_id: u32) -> bool {
         Ok(hook) => Some(hook),
         Err(err) => {
             println!("Failed to install hook: {}", err);
-            return false;
+            unhook_partial_install();
+            return HookInstallStatus::Failed;
         }
     };
 
@@ -117,11 +249,47 @@ pub unsafe extern "system" fn install_hook(thread_id: u32) -> bool {
             Ok(hook) => Some(hook),
             Err(err) => {
                 println!("Failed to install hook: {}", err);
-                return false;
+                unhook_partial_install();
+                return HookInstallStatus::Failed;
             }
         };
 
-    true
+    HookInstallStatus::Installed
+}
+
+/// rolls back whichever of `WIN_PROC_HOOK`/`WIN_PROC_RET_HOOK`/`GET_MESSAGE_HOOK` already
+/// installed earlier in the same `install_hook` call, so a later failure doesn't leave a partial
+/// hook set live while also tripping the `AlreadyInstalled` guard on every future call
+unsafe fn unhook_partial_install() {
+    if let Some(hook) = WIN_PROC_HOOK.take() {
+        let _ = UnhookWindowsHookEx(hook);
+    }
+    if let Some(hook) = WIN_PROC_RET_HOOK.take() {
+        let _ = UnhookWindowsHookEx(hook);
+    }
+    if let Some(hook) = GET_MESSAGE_HOOK.take() {
+        let _ = UnhookWindowsHookEx(hook);
+    }
+}
+
+/// unhooks any hook installed by `install_hook`/`install_cbt_hook` and resets the statics,
+/// called automatically from `DllMain` on `DLL_PROCESS_DETACH`
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "system" fn uninstall_hook() {
+    if let Some(hook) = WIN_PROC_HOOK.take() {
+        let _ = UnhookWindowsHookEx(hook);
+    }
+    if let Some(hook) = WIN_PROC_RET_HOOK.take() {
+        let _ = UnhookWindowsHookEx(hook);
+    }
+    if let Some(hook) = GET_MESSAGE_HOOK.take() {
+        let _ = UnhookWindowsHookEx(hook);
+    }
+    if let Some(hook) = CBT_HOOK.take() {
+        let _ = UnhookWindowsHookEx(hook);
+    }
 }
 
 /// # Safety
@@ -138,6 +306,7 @@ pub unsafe extern "system" fn DllMain(
         }
         DLL_PROCESS_DETACH => {
             println!("DllMain: DLL_PROCESS_DETACH");
+            uninstall_hook();
         }
         DLL_THREAD_ATTACH => {
             // println!("DllMain: DLL_THREAD_ATTACH");